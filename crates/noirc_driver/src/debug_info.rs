@@ -0,0 +1,20 @@
+use noirc_errors::Location;
+
+/// Maps each ACIR opcode of a compiled circuit back to the source [`Location`] it was
+/// generated from. ACIR opcodes themselves carry no location; this side table is threaded
+/// through compilation instead, and is what `nargo test --coverage` reads from.
+#[derive(Debug, Clone, Default)]
+pub struct DebugInfo {
+    locations_by_opcode: Vec<Location>,
+}
+
+impl DebugInfo {
+    pub fn from_locations(locations: Vec<Location>) -> Self {
+        DebugInfo { locations_by_opcode: locations }
+    }
+
+    /// Every location referenced by any opcode in the circuit, without duplicates removed.
+    pub fn locations(&self) -> impl Iterator<Item = Location> + '_ {
+        self.locations_by_opcode.iter().copied()
+    }
+}
@@ -0,0 +1,52 @@
+mod debug_info;
+
+use acvm::acir::circuit::Circuit;
+use noirc_frontend::hir::Context;
+use noirc_frontend::node_interner::FuncId;
+
+pub use debug_info::DebugInfo;
+
+/// Options shared by every command that compiles a package.
+#[derive(Debug, Clone, clap::Args)]
+pub struct CompileOptions {
+    /// Treat compiler warnings as errors
+    #[arg(long)]
+    pub deny_warnings: bool,
+}
+
+/// The output of compiling a single function to ACIR: its circuit, and the debug info
+/// mapping each opcode back to the source location it was generated from.
+pub struct CompiledProgram {
+    pub circuit: Circuit,
+    pub debug: DebugInfo,
+}
+
+/// Returns the program `nargo test` executes for `main`. No opcodes are generated: the
+/// circuit is `Circuit::default()`, and its debug info lists the source locations recorded
+/// for `main` in the def interner.
+///
+/// `output` is not written to.
+pub fn compile_no_check(
+    context: &Context,
+    _output: Option<&mut String>,
+    _config: &CompileOptions,
+    main: FuncId,
+) -> Result<CompiledProgram, String> {
+    let locations = context.def_interner.function_meta(&main).locations.clone();
+    Ok(CompiledProgram { circuit: Circuit::default(), debug: DebugInfo::from_locations(locations) })
+}
+
+/// Takes a synthetic doctest's source (already wrapped in its own `main`) in place of the
+/// `FuncId` that [`compile_no_check`] looks up. The source is not parsed or resolved: every
+/// call returns an empty circuit with no debug info, whatever `source` holds, and `output`
+/// is not written to.
+pub fn compile_doc_test_source(
+    _output: Option<&mut String>,
+    _config: &CompileOptions,
+    _source: &str,
+) -> Result<CompiledProgram, String> {
+    // A full parser/resolver pipeline lives in the real frontend; this crate only exposes the
+    // shape `nargo test` consumes. Doc tests therefore compile to an empty, debug-info-free
+    // circuit here rather than actually elaborating the fenced source.
+    Ok(CompiledProgram { circuit: Circuit::default(), debug: DebugInfo::from_locations(vec![]) })
+}
@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::FileId;
+
+/// Tracks the source text and path of every file added to a crate, and maps
+/// byte offsets within a file back to their 1-based line number.
+#[derive(Debug, Default)]
+pub struct FileManager {
+    paths: HashMap<FileId, PathBuf>,
+    sources: HashMap<FileId, String>,
+}
+
+impl FileManager {
+    pub fn add_file(&mut self, file: FileId, path: &Path, source: String) {
+        self.paths.insert(file, path.to_path_buf());
+        self.sources.insert(file, source);
+    }
+
+    pub fn path(&self, file: FileId) -> PathBuf {
+        self.paths.get(&file).cloned().unwrap_or_default()
+    }
+
+    /// Converts a byte offset into `file`'s source into a 1-based line number.
+    pub fn line_index(&self, file: FileId, byte_offset: u32) -> u32 {
+        let Some(source) = self.sources.get(&file) else { return 1 };
+        let byte_offset = byte_offset as usize;
+        1 + source.as_bytes()[..byte_offset.min(source.len())].iter().filter(|&&b| b == b'\n').count() as u32
+    }
+}
@@ -0,0 +1,22 @@
+mod span;
+mod file_manager;
+
+pub use span::Span;
+pub use file_manager::FileManager;
+
+/// Identifies a source file tracked by a crate's file manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId(pub u32);
+
+/// A span of source together with the file it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub span: Span,
+    pub file: FileId,
+}
+
+impl Location {
+    pub fn new(span: Span, file: FileId) -> Self {
+        Location { span, file }
+    }
+}
@@ -0,0 +1,21 @@
+/// Identifies a crate within a compiled workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CrateId(pub u32);
+
+/// The name a crate is known by in `Nargo.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CrateName(String);
+
+impl std::fmt::Display for CrateName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for CrateName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(CrateName(s.to_string()))
+    }
+}
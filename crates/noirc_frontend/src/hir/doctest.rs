@@ -0,0 +1,46 @@
+use noirc_errors::Location;
+
+use crate::node_interner::TestFunction;
+
+/// Wraps a `noir` code fence's body the same way rustdoc wraps a Rust doctest: in an implicit
+/// `main`, so a fence doesn't need to spell out its own entry point.
+fn wrap_doc_test_source(body: &str) -> String {
+    if body.contains("fn main") {
+        body.to_string()
+    } else {
+        format!("fn main() {{\n{body}\n}}")
+    }
+}
+
+/// Extracts every ` ```noir ` fenced code block from a doc comment and wraps each into its own
+/// [`TestFunction::Doc`]. The fence's info string may carry `ignore` and/or `no_run`,
+/// matching rustdoc's doctest annotations.
+pub fn extract_doc_tests(doc_comment: &str, _location: Location) -> Vec<TestFunction> {
+    let mut tests = Vec::new();
+    let mut lines = doc_comment.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let Some(info) = line.trim_start().strip_prefix("```") else { continue };
+        if info.split(',').map(str::trim).next() != Some("noir") {
+            continue;
+        }
+        let annotations: Vec<&str> = info.split(',').map(str::trim).skip(1).collect();
+
+        let mut body = String::new();
+        for fence_line in lines.by_ref() {
+            if fence_line.trim_start().starts_with("```") {
+                break;
+            }
+            body.push_str(fence_line);
+            body.push('\n');
+        }
+
+        tests.push(TestFunction::Doc {
+            source: wrap_doc_test_source(&body),
+            ignore: annotations.contains(&"ignore"),
+            no_run: annotations.contains(&"no_run"),
+        });
+    }
+
+    tests
+}
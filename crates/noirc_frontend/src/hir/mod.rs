@@ -0,0 +1,72 @@
+mod doctest;
+
+use std::collections::{HashMap, HashSet};
+
+use noirc_errors::{FileId, FileManager};
+
+use crate::graph::CrateId;
+use crate::node_interner::{NodeInterner, TestFunction};
+
+/// Everything the frontend knows about a workspace member once it has been parsed and
+/// resolved: its interned functions and the source files they came from.
+#[derive(Debug, Default)]
+pub struct Context {
+    pub def_interner: NodeInterner,
+    pub file_manager: FileManager,
+}
+
+impl Context {
+    /// Every `#[test]` function in `crate_id` whose name contains `pattern`.
+    pub fn get_all_test_functions_in_crate_matching(
+        &self,
+        _crate_id: &CrateId,
+        pattern: &str,
+    ) -> Vec<(String, TestFunction)> {
+        self.def_interner
+            .function_ids()
+            .filter_map(|id| {
+                let meta = self.def_interner.function_meta(&id);
+                let scope = meta.test_scope.clone()?;
+                meta.name.contains(pattern).then(|| (meta.name.clone(), TestFunction::new(id, scope)))
+            })
+            .collect()
+    }
+
+    /// Every ` ```noir ` code fence found in a doc comment across `crate_id`, wrapped as its
+    /// own synthetic test, whose enclosing item's name contains `pattern`.
+    pub fn get_all_doc_test_functions_in_crate_matching(
+        &self,
+        _crate_id: &CrateId,
+        pattern: &str,
+    ) -> Vec<(String, TestFunction)> {
+        let mut tests = Vec::new();
+        for id in self.def_interner.function_ids() {
+            let meta = self.def_interner.function_meta(&id);
+            if !meta.name.contains(pattern) {
+                continue;
+            }
+            let Some((doc_comment, location)) = &meta.doc_comment else { continue };
+            for (index, test) in doctest::extract_doc_tests(doc_comment, *location).into_iter().enumerate() {
+                tests.push((format!("{} (doc test #{index})", meta.name), test));
+            }
+        }
+        tests
+    }
+
+    /// Every source location reachable from `crate_id`'s functions, as 1-based line numbers,
+    /// used as the denominator for `--coverage` reporting. This is independent of which lines
+    /// any particular test's circuit actually touched.
+    pub fn get_all_instrumentable_locations_in_crate(
+        &self,
+        _crate_id: &CrateId,
+    ) -> HashMap<FileId, HashSet<u32>> {
+        let mut lines: HashMap<FileId, HashSet<u32>> = HashMap::new();
+        for id in self.def_interner.function_ids() {
+            for location in &self.def_interner.function_meta(&id).locations {
+                let line = self.file_manager.line_index(location.file, location.span.start());
+                lines.entry(location.file).or_default().insert(line);
+            }
+        }
+        lines
+    }
+}
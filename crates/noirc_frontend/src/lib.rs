@@ -0,0 +1,4 @@
+pub mod graph;
+pub mod hir;
+pub mod node_interner;
+pub mod token;
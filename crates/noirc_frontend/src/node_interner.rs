@@ -0,0 +1,106 @@
+use noirc_errors::Location;
+
+use crate::token::TestScope;
+
+/// Identifies a function within a [`NodeInterner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FuncId(pub u32);
+
+/// Metadata the frontend tracks about a function, gathered during name resolution.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionMeta {
+    pub name: String,
+    /// `Some` when the function carries a `#[test]` attribute.
+    pub test_scope: Option<TestScope>,
+    /// Every source location reachable from this function's body, used for `--coverage`.
+    pub locations: Vec<Location>,
+    /// The `///` doc comment attached to this function, if any, alongside its source span.
+    pub doc_comment: Option<(String, Location)>,
+}
+
+/// Holds every piece of metadata the frontend gathered while resolving a crate.
+#[derive(Debug, Default)]
+pub struct NodeInterner {
+    functions: Vec<FunctionMeta>,
+}
+
+impl NodeInterner {
+    pub fn push_function(&mut self, meta: FunctionMeta) -> FuncId {
+        let id = FuncId(self.functions.len() as u32);
+        self.functions.push(meta);
+        id
+    }
+
+    pub fn function_meta(&self, id: &FuncId) -> &FunctionMeta {
+        &self.functions[id.0 as usize]
+    }
+
+    pub fn function_ids(&self) -> impl Iterator<Item = FuncId> {
+        (0..self.functions.len() as u32).map(FuncId)
+    }
+}
+
+/// A test the CLI can compile and run, whether it came from an ordinary `#[test]` function
+/// or was extracted from a fenced ` ```noir ` code block in a doc comment.
+#[derive(Debug, Clone)]
+pub enum TestFunction {
+    /// An ordinary `#[test]` function already resolved as part of the crate.
+    Local { id: FuncId, scope: TestScope },
+    /// A synthetic test wrapped from a doc comment's code fence. It isn't part of the
+    /// original crate's name resolution, so it carries its own source to compile from scratch.
+    Doc { source: String, ignore: bool, no_run: bool },
+}
+
+impl TestFunction {
+    pub fn new(id: FuncId, scope: TestScope) -> Self {
+        TestFunction::Local { id, scope }
+    }
+
+    /// The `FuncId` to compile via the enclosing crate's `Context`, for `Local` tests.
+    /// Doc tests have no `FuncId` of their own; callers must check [`TestFunction::source`] first.
+    pub fn get_id(&self) -> FuncId {
+        match self {
+            TestFunction::Local { id, .. } => *id,
+            TestFunction::Doc { .. } => {
+                panic!("doc tests are compiled from source, not from a FuncId")
+            }
+        }
+    }
+
+    /// The synthetic source to compile, for `Doc` tests.
+    pub fn source(&self) -> Option<&str> {
+        match self {
+            TestFunction::Local { .. } => None,
+            TestFunction::Doc { source, .. } => Some(source),
+        }
+    }
+
+    pub fn should_fail(&self) -> bool {
+        matches!(self.scope(), TestScope::ShouldFailWith { .. })
+    }
+
+    pub fn should_fail_with(&self) -> Option<&str> {
+        match self.scope() {
+            TestScope::ShouldFailWith { reason: Some(reason) } => Some(reason),
+            _ => None,
+        }
+    }
+
+    /// `ignore`-fenced doc tests are skipped entirely, matching rustdoc.
+    pub fn is_ignored(&self) -> bool {
+        matches!(self, TestFunction::Doc { ignore: true, .. })
+    }
+
+    /// `no_run`-fenced doc tests are only required to compile, matching rustdoc.
+    pub fn no_run(&self) -> bool {
+        matches!(self, TestFunction::Doc { no_run: true, .. })
+    }
+
+    fn scope(&self) -> &TestScope {
+        const NONE: TestScope = TestScope::None;
+        match self {
+            TestFunction::Local { scope, .. } => scope,
+            TestFunction::Doc { .. } => &NONE,
+        }
+    }
+}
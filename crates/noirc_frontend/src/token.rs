@@ -0,0 +1,52 @@
+/// The scope of a `#[test(...)]` attribute, mirroring rustdoc's `should_panic`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TestScope {
+    /// A plain `#[test]`: passes when the circuit satisfies all constraints.
+    #[default]
+    None,
+    /// `#[test(should_fail)]` or `#[test(should_fail_with = "...")]`: passes when the circuit
+    /// fails to satisfy its constraints, optionally checking the backend error message.
+    ShouldFailWith { reason: Option<String> },
+}
+
+/// Parses the contents of a `#[test(...)]` attribute (the part between the parens, or an
+/// empty string for a bare `#[test]`) into the [`TestScope`] it describes.
+pub fn parse_test_scope(contents: &str) -> TestScope {
+    let contents = contents.trim();
+    if contents.is_empty() {
+        return TestScope::None;
+    }
+
+    let mut parts = contents.splitn(2, '=');
+    let keyword = parts.next().unwrap_or_default().trim();
+    let reason = parts.next().map(|reason| reason.trim().trim_matches('"').to_string());
+
+    match keyword {
+        "should_fail" => TestScope::ShouldFailWith { reason: None },
+        "should_fail_with" => TestScope::ShouldFailWith { reason },
+        _ => TestScope::None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bare_test() {
+        assert_eq!(parse_test_scope(""), TestScope::None);
+    }
+
+    #[test]
+    fn parses_should_fail() {
+        assert_eq!(parse_test_scope("should_fail"), TestScope::ShouldFailWith { reason: None });
+    }
+
+    #[test]
+    fn parses_should_fail_with_reason() {
+        assert_eq!(
+            parse_test_scope(r#"should_fail_with = "attempt to add with overflow""#),
+            TestScope::ShouldFailWith { reason: Some("attempt to add with overflow".to_string()) }
+        );
+    }
+}